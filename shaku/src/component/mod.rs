@@ -0,0 +1,46 @@
+//! Components are the building blocks registered into a
+//! [`Container`](../container/struct.Container.html).
+
+use std::any::Any;
+
+use crate::container::Container;
+
+/// Marker trait, automatically implemented for every `'static` type, used as
+/// the supertrait of the traits exposed through a
+/// [`Container`](../container/struct.Container.html) (e.g. `trait Foo: Interface`).
+///
+/// With the `thread_safe` Cargo feature enabled, `Interface` additionally
+/// requires `Send + Sync`, so that a built `Container` can be wrapped in an
+/// `Arc` and shared across threads or an async executor. This is off by
+/// default so single-threaded users aren't forced to make every parameter
+/// `Send + Sync`.
+#[cfg(not(feature = "thread_safe"))]
+pub trait Interface: Any {}
+#[cfg(not(feature = "thread_safe"))]
+impl<T: Any> Interface for T {}
+
+#[cfg(feature = "thread_safe")]
+pub trait Interface: Any + Send + Sync {}
+#[cfg(feature = "thread_safe")]
+impl<T: Any + Send + Sync> Interface for T {}
+
+/// A `Component` knows how to build an instance of its `Interface`, resolving
+/// any `#[inject]` dependency from the [`Container`] it is given.
+///
+/// This trait is normally implemented through `#[derive(Component)]`
+/// (see the `shaku_derive` crate) rather than by hand. Generic structs are
+/// supported, both by the derive macro (e.g. `#[interface(Serializer<T>)]`)
+/// and by hand-written impls: every instantiation is still `'static`, and
+/// registration keys off the concrete, monomorphized `Interface`; see
+/// [`ContainerBuilder::register_type`](../container/struct.ContainerBuilder.html#method.register_type)
+/// for an example.
+///
+/// [`Container`]: ../container/struct.Container.html
+pub trait Component: Any {
+    /// The trait object this Component is registered as.
+    type Interface: Interface + ?Sized;
+
+    /// Build an instance of `Self::Interface`, resolving `#[inject]` fields
+    /// from `container`.
+    fn build(container: &Container) -> Box<Self::Interface>;
+}
@@ -1,10 +1,15 @@
 //! Implementation of a `ContainerBuilder`
 
 use std::any::type_name;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
 
-use crate::component::Component;
-use crate::container::{Container, Map, RegisteredType};
-use crate::result::Result as DIResult;
+use crate::component::{Component, Interface};
+use crate::container::{Bindings, Container, InstanceFns, Key, Lifetime, Map, RegisteredType};
+use crate::result::{Error, Result as DIResult};
+use crate::sync_bound::MaybeSendSync;
+
+type SingletonValidator = Box<dyn FnOnce(&Container) -> DIResult<()>>;
 
 /// Build a [Container](struct.Container.html) registering components
 /// with or without parameters.
@@ -17,11 +22,22 @@ use crate::result::Result as DIResult;
 /// [ContainerBuilder::build()](struct.ContainerBuilder.html#method.build) for more details.
 pub struct ContainerBuilder {
     map: Map,
+    instances: Map,
+    // One entry per `register_type`/`register_named_type` call, used by
+    // `build` to eagerly resolve singletons and surface a missing transitive
+    // `#[inject]` dependency as an `Error` instead of deferring it to
+    // whichever `resolve` call happens to need it first. A no-op for
+    // registrations that never become a singleton.
+    singleton_validators: Vec<SingletonValidator>,
 }
 
 impl Default for ContainerBuilder {
     fn default() -> Self {
-        ContainerBuilder { map: Map::new() }
+        ContainerBuilder {
+            map: Map::new(),
+            instances: Map::new(),
+            singleton_validators: Vec::new(),
+        }
     }
 }
 
@@ -39,34 +55,241 @@ impl ContainerBuilder {
     /// [with_named_parameter()](struct.RegisteredType.html#method.with_named_parameter)
     /// or [with_typed_parameter()](struct.RegisteredType.html#method.with_typed_parameter)
     /// to add parameters to be used to instantiate this Component.
+    ///
+    /// `C` can be generic: since storage is keyed by the `TypeId` of the
+    /// fully monomorphized `C::Interface`, two instantiations of the same
+    /// generic Component (e.g. `JsonSerializer<User>` and
+    /// `JsonSerializer<Order>`) register and resolve independently, as if
+    /// they were unrelated types. Such a Component can use
+    /// `#[derive(Component)]` with `#[interface(Serializer<T>)]`, or implement
+    /// [`Component`](../component/trait.Component.html) by hand per
+    /// instantiation, as below.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::marker::PhantomData;
+    /// use shaku::component::{Component, Interface};
+    /// use shaku::container::Container;
+    ///
+    /// trait Serializer<T>: Interface {
+    ///     fn serialize(&self, value: &T) -> String;
+    /// }
+    ///
+    /// struct JsonSerializer<T>(PhantomData<T>);
+    ///
+    /// struct User(String);
+    /// struct Order(u32);
+    ///
+    /// impl Component for JsonSerializer<User> {
+    ///     type Interface = dyn Serializer<User>;
+    ///     fn build(_: &Container) -> Box<Self::Interface> {
+    ///         Box::new(JsonSerializer(PhantomData))
+    ///     }
+    /// }
+    /// impl Serializer<User> for JsonSerializer<User> {
+    ///     fn serialize(&self, value: &User) -> String {
+    ///         format!("{{\"name\":\"{}\"}}", value.0)
+    ///     }
+    /// }
+    ///
+    /// impl Component for JsonSerializer<Order> {
+    ///     type Interface = dyn Serializer<Order>;
+    ///     fn build(_: &Container) -> Box<Self::Interface> {
+    ///         Box::new(JsonSerializer(PhantomData))
+    ///     }
+    /// }
+    /// impl Serializer<Order> for JsonSerializer<Order> {
+    ///     fn serialize(&self, value: &Order) -> String {
+    ///         format!("{{\"id\":{}}}", value.0)
+    ///     }
+    /// }
+    ///
+    /// let mut builder = shaku::ContainerBuilder::new();
+    /// builder.register_type::<JsonSerializer<User>>();
+    /// builder.register_type::<JsonSerializer<Order>>();
+    ///
+    /// let container = builder.build().unwrap();
+    /// assert_eq!(
+    ///     container.resolve::<dyn Serializer<User>>().unwrap().serialize(&User("Ann".to_string())),
+    ///     "{\"name\":\"Ann\"}"
+    /// );
+    /// assert_eq!(
+    ///     container.resolve::<dyn Serializer<Order>>().unwrap().serialize(&Order(42)),
+    ///     "{\"id\":42}"
+    /// );
+    /// ```
     pub fn register_type<C: Component>(&mut self) -> &mut RegisteredType<C::Interface> {
+        self.register_binding::<C>(Key::Default)
+    }
+
+    /// Register a new component under `name`, allowing several Components to
+    /// be registered for the same `Interface` and later picked between with
+    /// [`Container::resolve_named`](struct.Container.html#method.resolve_named).
+    /// If a component was already registered under that name, the old one is
+    /// replaced; it does not affect the unnamed (default) registration.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use shaku_derive::Component;
+    ///
+    /// use shaku::component::Interface;
+    ///
+    /// trait Repo: Interface { fn name(&self) -> &'static str; }
+    ///
+    /// #[derive(Component)]
+    /// #[interface(Repo)]
+    /// struct SqliteRepo;
+    /// impl Repo for SqliteRepo { fn name(&self) -> &'static str { "sqlite" } }
+    ///
+    /// #[derive(Component)]
+    /// #[interface(Repo)]
+    /// struct PostgresRepo;
+    /// impl Repo for PostgresRepo { fn name(&self) -> &'static str { "postgres" } }
+    ///
+    /// let mut builder = shaku::ContainerBuilder::new();
+    /// builder.register_named_type::<SqliteRepo>("sqlite");
+    /// builder.register_named_type::<PostgresRepo>("postgres");
+    ///
+    /// let container = builder.build().unwrap();
+    /// assert_eq!(container.resolve_named::<dyn Repo>("postgres").unwrap().name(), "postgres");
+    /// assert_eq!(container.resolve_named::<dyn Repo>("sqlite").unwrap().name(), "sqlite");
+    /// ```
+    pub fn register_named_type<C: Component>(
+        &mut self,
+        name: &str,
+    ) -> &mut RegisteredType<C::Interface> {
+        self.register_binding::<C>(Key::Named(name.to_string()))
+    }
+
+    fn register_binding<C: Component>(&mut self, key: Key) -> &mut RegisteredType<C::Interface> {
         // Get the type name from the turbo-fish input
         let component_type_name = type_name::<C>().to_string();
         let interface_type_name = type_name::<C::Interface>();
 
         let registered_type = RegisteredType::new(component_type_name, C::build);
 
-        let old_value = self
-            .map
-            .insert::<RegisteredType<C::Interface>>(registered_type);
+        if self.map.get_mut::<Bindings<C::Interface>>().is_none() {
+            self.map.insert::<Bindings<C::Interface>>(Bindings::new());
+        }
+        let bindings = self.map.get_mut::<Bindings<C::Interface>>().unwrap();
+
+        let old_value = bindings.entries.insert(key.clone(), registered_type);
         if let Some(old_value) = old_value {
             warn!(
-                "::shaku::ContainerBuilder::register_type::warning trait {:?} already had Component '{:?}) registered to it",
+                "::shaku::ContainerBuilder::register_type::warning trait {:?} already had Component '{:?}') registered to it ({:?})",
                 interface_type_name,
-                &old_value.component
+                &old_value.component,
+                key,
             );
         }
 
-        self.map.get_mut::<RegisteredType<C::Interface>>().unwrap()
+        let validation_key = key.clone();
+        self.singleton_validators
+            .push(Box::new(move |container: &Container| {
+                let is_singleton = container
+                    .map
+                    .get::<Bindings<C::Interface>>()
+                    .and_then(|bindings| bindings.entries.get(&validation_key))
+                    .map(|registered| registered.lifetime == Lifetime::Singleton)
+                    .unwrap_or(false);
+
+                if !is_singleton {
+                    return Ok(());
+                }
+
+                // `Component::build` has no way to return a `Result`, so a
+                // missing transitive `#[inject]` dependency surfaces as a
+                // panic from deep inside it; catch that here and turn it
+                // into a regular `Error` instead of letting it escape `build`.
+                let resolved = panic::catch_unwind(AssertUnwindSafe(|| {
+                    container.resolve_binding::<C::Interface>(&validation_key)
+                }))
+                .map_err(|_| {
+                    Error::ResolveError(format!(
+                        "singleton Component for interface '{}' panicked while resolving a transitive #[inject] dependency",
+                        type_name::<C::Interface>()
+                    ))
+                })?;
+
+                resolved.map(|_| ())
+            }));
+
+        self.map
+            .get_mut::<Bindings<C::Interface>>()
+            .unwrap()
+            .entries
+            .get_mut(&key)
+            .unwrap()
+    }
+
+    /// Register an already-constructed instance for `I`, instead of a
+    /// [`Component`](../component/trait.Component.html) the container would
+    /// build itself. The same instance is handed out to every `resolve::<I>()`
+    /// call and to any `#[inject]` dependency of other Components.
+    ///
+    /// Useful to put a value this container did not construct (a third-party
+    /// struct, a value produced by `main`, a mock in tests) into the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use shaku::component::Interface;
+    ///
+    /// trait Logger: Interface { fn log(&self, msg: &str) -> String; }
+    ///
+    /// struct PrefixLogger(&'static str);
+    /// impl Logger for PrefixLogger {
+    ///     fn log(&self, msg: &str) -> String { format!("{}: {}", self.0, msg) }
+    /// }
+    ///
+    /// let mut builder = shaku::ContainerBuilder::new();
+    /// builder.register_instance::<dyn Logger>(Arc::new(PrefixLogger("app")));
+    ///
+    /// let container = builder.build().unwrap();
+    /// assert_eq!(container.resolve::<dyn Logger>().unwrap().log("hi"), "app: hi");
+    /// ```
+    pub fn register_instance<I: Interface + ?Sized>(&mut self, value: Arc<I>) -> &mut Self {
+        self.register_instance_fn::<I>(move |_| value)
+    }
+
+    /// Like [`register_instance`](#method.register_instance), but lazily
+    /// builds the instance the first time it is resolved instead of
+    /// requiring one up front. `build` is called at most once.
+    pub fn register_instance_fn<I: Interface + ?Sized>(
+        &mut self,
+        build: impl FnOnce(&Container) -> Arc<I> + MaybeSendSync + 'static,
+    ) -> &mut Self {
+        if self.instances.get_mut::<InstanceFns<I>>().is_none() {
+            self.instances.insert::<InstanceFns<I>>(InstanceFns::new());
+        }
+        self.instances
+            .get_mut::<InstanceFns<I>>()
+            .unwrap()
+            .entries
+            .insert(Key::Default, Box::new(build));
+
+        self
     }
 
     /// Parse this `ContainerBuilder` content to check if all the registrations are valid.
     /// If so, consume this `ContainerBuilder` to build a [Container](struct.Container.html).
     ///
+    /// Components marked with
+    /// [`as_singleton()`](struct.RegisteredType.html#method.as_singleton) are
+    /// built right here instead of waiting for their first `resolve`, so a
+    /// missing transitive `#[inject]` dependency is reported as an `Error`
+    /// from `build` instead of surfacing later, possibly far from the
+    /// registration that caused it; the built instance is then cached on the
+    /// `Container`, same as a lazily-built singleton would be. Transient
+    /// components are unaffected and stay lazy.
+    ///
     /// # Errors
-    /// None for the moment, since v0.3.0 we try to fail at compile time for all possible invalid registrations.
-    /// We still kept the signature to stabilize API in case we introduce some fancier validation of a ContainerBuilder
-    /// in a later stage.
+    /// `Err` if a singleton (or one of its transitive `#[inject]`
+    /// dependencies) fails to resolve, e.g. because it, or something it
+    /// depends on, was never registered.
     ///
     /// # Examples
     ///
@@ -116,9 +339,88 @@ impl ContainerBuilder {
     /// let foo = container.resolve::<dyn FooDuplicate>();
     /// assert!(foo.is_ok());
     /// assert_eq!(foo.unwrap().foo(), "FooDuplicateImpl2".to_string());
+    ///
+    /// // A singleton whose #[inject] dependency was never registered is
+    /// // caught here, instead of only failing whichever `resolve` needs it
+    /// // first.
+    /// trait Logger: Interface { fn log(&self, msg: &str) -> String; }
+    /// trait Greeter: Interface { fn greet(&self) -> String; }
+    ///
+    /// struct GreeterImpl { logger: std::sync::Arc<dyn Logger> }
+    /// impl Greeter for GreeterImpl {
+    ///     fn greet(&self) -> String { self.logger.log("hi") }
+    /// }
+    /// impl shaku::Component for GreeterImpl {
+    ///     type Interface = dyn Greeter;
+    ///     fn build(container: &shaku::Container) -> Box<Self::Interface> {
+    ///         Box::new(GreeterImpl {
+    ///             logger: container.resolve::<dyn Logger>().expect("Logger dependency"),
+    ///         })
+    ///     }
+    /// }
+    ///
+    /// let mut builder = shaku::ContainerBuilder::new();
+    /// builder.register_type::<GreeterImpl>().as_singleton();
+    /// // `Logger` is never registered.
+    ///
+    /// let container = builder.build();
+    /// assert!(container.is_err());
     /// ```
     ///
     pub fn build(self) -> DIResult<Container> {
-        Ok(Container::new(self.map))
+        let container = Container::new(self.map, self.instances);
+        for validate in self.singleton_validators {
+            validate(&container)?;
+        }
+        Ok(container)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    trait Logger: Interface {
+        fn log(&self, msg: &str) -> String;
+    }
+
+    struct PrefixLogger(&'static str);
+    impl Logger for PrefixLogger {
+        fn log(&self, msg: &str) -> String {
+            format!("{}: {}", self.0, msg)
+        }
+    }
+
+    #[test]
+    fn register_instance_shares_the_same_value_across_resolves() {
+        let mut builder = ContainerBuilder::new();
+        builder.register_instance::<dyn Logger>(Arc::new(PrefixLogger("app")));
+        let container = builder.build().unwrap();
+
+        let first = container.resolve::<dyn Logger>().unwrap();
+        let second = container.resolve::<dyn Logger>().unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(first.log("hi"), "app: hi");
+    }
+
+    #[test]
+    fn register_instance_fn_builds_at_most_once() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let calls_in_closure = calls.clone();
+
+        let mut builder = ContainerBuilder::new();
+        builder.register_instance_fn::<dyn Logger>(move |_| {
+            calls_in_closure.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Arc::new(PrefixLogger("lazy"))
+        });
+        let container = builder.build().unwrap();
+
+        // `register_instance_fn` is lazy: the closure has not run yet.
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        let first = container.resolve::<dyn Logger>().unwrap();
+        let second = container.resolve::<dyn Logger>().unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
     }
 }
@@ -0,0 +1,46 @@
+//! A small type-keyed map, storing at most one value per concrete type.
+//!
+//! This underlies [`Container`](struct.Container.html)'s component storage:
+//! each registration is keyed purely by the `TypeId` of the Rust type used to
+//! store it (e.g. `Bindings<dyn Foo>`), not by any value of that type.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+use crate::sync_bound::MaybeSendSync;
+
+#[cfg(not(feature = "thread_safe"))]
+type Stored = dyn Any;
+#[cfg(feature = "thread_safe")]
+type Stored = dyn Any + Send + Sync;
+
+pub(crate) struct Map {
+    inner: HashMap<TypeId, Box<Stored>>,
+}
+
+impl Map {
+    pub(crate) fn new() -> Self {
+        Map {
+            inner: HashMap::new(),
+        }
+    }
+
+    /// Insert `value`, returning the previous value stored for this type, if any.
+    pub(crate) fn insert<T: Any + MaybeSendSync>(&mut self, value: T) -> Option<T> {
+        self.inner
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .map(|old| *old.downcast::<T>().expect("shaku: Map type mismatch"))
+    }
+
+    pub(crate) fn get<T: Any + MaybeSendSync>(&self) -> Option<&T> {
+        self.inner
+            .get(&TypeId::of::<T>())
+            .map(|v| v.downcast_ref::<T>().expect("shaku: Map type mismatch"))
+    }
+
+    pub(crate) fn get_mut<T: Any + MaybeSendSync>(&mut self) -> Option<&mut T> {
+        self.inner
+            .get_mut(&TypeId::of::<T>())
+            .map(|v| v.downcast_mut::<T>().expect("shaku: Map type mismatch"))
+    }
+}
@@ -0,0 +1,441 @@
+//! Build and resolve a dependency graph of [`Component`](../component/trait.Component.html)s.
+
+mod container_builder;
+mod map;
+mod sync;
+
+pub use self::container_builder::ContainerBuilder;
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::component::Interface;
+use crate::parameter::ParameterMap;
+use crate::result::{Error, Result as DIResult};
+use crate::sync_bound::MaybeSendSync;
+
+pub(crate) use self::map::Map;
+pub(crate) use self::sync::Lock;
+
+#[cfg(not(feature = "thread_safe"))]
+type InstanceBuilder<I> = Box<dyn FnOnce(&Container) -> Arc<I>>;
+// `InstanceFns<I>` is stored in a `Map` bounded by `MaybeSendSync`, which
+// requires `Send + Sync` under `thread_safe`; a `Box<dyn FnOnce + Send>`
+// alone is `Send` but not `Sync`, so the trait object itself needs `+ Sync`
+// too (the closures handed to `register_instance_fn` already satisfy it,
+// since they're bounded by `MaybeSendSync` there as well).
+#[cfg(feature = "thread_safe")]
+type InstanceBuilder<I> = Box<dyn FnOnce(&Container) -> Arc<I> + Send + Sync>;
+
+/// How many times a Component is built over the lifetime of a [`Container`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Lifetime {
+    /// Built again on every `resolve` call (the default).
+    Transient,
+    /// Built once, the first time it is resolved, and shared from then on.
+    Singleton,
+}
+
+/// Identifies one binding for a given interface: either the default
+/// (unnamed) registration, or one of several named alternatives created via
+/// [`ContainerBuilder::register_named_type`](struct.ContainerBuilder.html#method.register_named_type).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum Key {
+    Default,
+    Named(String),
+}
+
+/// All the [`RegisteredType`](struct.RegisteredType.html)s registered for a
+/// single interface `I`, keyed by [`Key`] so several implementations of the
+/// same trait can coexist side by side.
+pub(crate) struct Bindings<I: ?Sized + Interface> {
+    pub(crate) entries: HashMap<Key, RegisteredType<I>>,
+}
+
+impl<I: ?Sized + Interface> Bindings<I> {
+    fn new() -> Self {
+        Bindings {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+/// A Component registered into a [`ContainerBuilder`](struct.ContainerBuilder.html),
+/// together with the parameters attached to it (see the caveat on
+/// [`with_named_parameter`](#method.with_named_parameter)).
+pub struct RegisteredType<I: ?Sized + Interface> {
+    pub(crate) component: String,
+    builder: fn(&Container) -> Box<I>,
+    pub(crate) parameters: ParameterMap,
+    pub(crate) lifetime: Lifetime,
+}
+
+impl<I: ?Sized + Interface> RegisteredType<I> {
+    pub(crate) fn new(component: String, builder: fn(&Container) -> Box<I>) -> Self {
+        RegisteredType {
+            component,
+            builder,
+            parameters: ParameterMap::new(),
+            lifetime: Lifetime::Transient,
+        }
+    }
+
+    /// Attach a parameter identified by name to this registration.
+    ///
+    /// Not consulted when the Component is built: `Component::build` only receives the `&Container`,
+    /// with no way to reach the `ParameterMap` attached here, so no
+    /// Component — hand-written or generated by `#[derive(Component)]` —
+    /// can currently read a parameter set this way. `#[derive(Component)]`
+    /// always fills non-`#[inject]` fields with `Default::default()`.
+    pub fn with_named_parameter<V: Any + MaybeSendSync>(
+        &mut self,
+        name: &str,
+        value: V,
+    ) -> &mut Self {
+        self.parameters.insert_with_name(name, value);
+        self
+    }
+
+    /// Attach a parameter identified by its type to this registration.
+    ///
+    /// Not consulted when the Component is built: see the note on
+    /// [`with_named_parameter`](#method.with_named_parameter).
+    pub fn with_typed_parameter<V: Any + MaybeSendSync>(&mut self, value: V) -> &mut Self {
+        self.parameters.insert_with_type(value);
+        self
+    }
+
+    /// Make this Component a singleton: built once, eagerly by
+    /// [`ContainerBuilder::build`](struct.ContainerBuilder.html#method.build),
+    /// and shared (via `Arc`) from then on instead of being rebuilt on every
+    /// resolve.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use shaku_derive::Component;
+    ///
+    /// use shaku::component::Interface;
+    ///
+    /// trait Counter: Interface { fn id(&self) -> usize; }
+    ///
+    /// #[derive(Component)]
+    /// #[interface(Counter)]
+    /// struct CounterImpl { id: usize }
+    /// impl Counter for CounterImpl { fn id(&self) -> usize { self.id } }
+    ///
+    /// let mut builder = shaku::ContainerBuilder::new();
+    /// builder.register_type::<CounterImpl>().as_singleton();
+    ///
+    /// let container = builder.build().unwrap();
+    /// let first = container.resolve::<dyn Counter>().unwrap();
+    /// let second = container.resolve::<dyn Counter>().unwrap();
+    /// assert!(Arc::ptr_eq(&first, &second));
+    /// ```
+    pub fn as_singleton(&mut self) -> &mut Self {
+        self.lifetime = Lifetime::Singleton;
+        self
+    }
+}
+
+/// The singletons already built for a single interface `I`, keyed the same
+/// way as [`Bindings`] so a named singleton and the default one can be
+/// cached independently.
+struct SingletonCache<I: ?Sized + Interface> {
+    entries: HashMap<Key, Arc<I>>,
+}
+
+impl<I: ?Sized + Interface> SingletonCache<I> {
+    fn new() -> Self {
+        SingletonCache {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+/// A closure producing an instance of `I`, registered via
+/// [`ContainerBuilder::register_instance`](struct.ContainerBuilder.html#method.register_instance)
+/// or [`register_instance_fn`](struct.ContainerBuilder.html#method.register_instance_fn),
+/// to be run at most once (the result is then kept in the singleton cache,
+/// like any other singleton).
+pub(crate) struct InstanceFns<I: ?Sized + Interface> {
+    pub(crate) entries: HashMap<Key, InstanceBuilder<I>>,
+}
+
+impl<I: ?Sized + Interface> InstanceFns<I> {
+    pub(crate) fn new() -> Self {
+        InstanceFns {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+/// A built dependency graph, produced by
+/// [`ContainerBuilder::build`](struct.ContainerBuilder.html#method.build).
+///
+/// With the `thread_safe` Cargo feature enabled, `Container` is `Send + Sync`
+/// and can be wrapped in an `Arc` to be shared across a thread pool or async
+/// tasks; by default it stays single-threaded.
+pub struct Container {
+    map: Map,
+    instance_fns: Lock<Map>,
+    singletons: Lock<Map>,
+}
+
+impl Container {
+    pub(crate) fn new(map: Map, instance_fns: Map) -> Self {
+        Container {
+            map,
+            instance_fns: Lock::new(instance_fns),
+            singletons: Lock::new(Map::new()),
+        }
+    }
+
+    /// Resolve the Component registered for `I`. Transient components are
+    /// built fresh on every call; singletons and registered instances are
+    /// built once and shared.
+    pub fn resolve<I: Interface + ?Sized>(&self) -> DIResult<Arc<I>> {
+        self.resolve_binding::<I>(&Key::Default)
+    }
+
+    /// Resolve the Component registered for `I` under `name`, as registered
+    /// via [`ContainerBuilder::register_named_type`](struct.ContainerBuilder.html#method.register_named_type).
+    pub fn resolve_named<I: Interface + ?Sized>(&self, name: &str) -> DIResult<Arc<I>> {
+        self.resolve_binding::<I>(&Key::Named(name.to_string()))
+    }
+
+    fn resolve_binding<I: Interface + ?Sized>(&self, key: &Key) -> DIResult<Arc<I>> {
+        let cached = self.singletons.with(|singletons| {
+            singletons
+                .get::<SingletonCache<I>>()
+                .and_then(|cache| cache.entries.get(key))
+                .cloned()
+        });
+        if let Some(cached) = cached {
+            return Ok(cached);
+        }
+
+        let pending_instance_fn = self.instance_fns.with_mut(|instance_fns| {
+            instance_fns
+                .get_mut::<InstanceFns<I>>()
+                .and_then(|fns| fns.entries.remove(key))
+        });
+
+        if let Some(build) = pending_instance_fn {
+            let instance = build(self);
+            self.cache_singleton::<I>(key, instance.clone());
+            return Ok(instance);
+        }
+
+        let registered = self
+            .map
+            .get::<Bindings<I>>()
+            .and_then(|bindings| bindings.entries.get(key))
+            .ok_or_else(|| {
+                Error::ResolveError(format!(
+                    "no Component registered for interface '{}' ({:?})",
+                    std::any::type_name::<I>(),
+                    key
+                ))
+            })?;
+
+        let instance: Arc<I> = Arc::from((registered.builder)(self));
+
+        if registered.lifetime == Lifetime::Singleton {
+            self.cache_singleton::<I>(key, instance.clone());
+        }
+
+        Ok(instance)
+    }
+
+    fn cache_singleton<I: Interface + ?Sized>(&self, key: &Key, instance: Arc<I>) {
+        self.singletons.with_mut(|singletons| {
+            if singletons.get_mut::<SingletonCache<I>>().is_none() {
+                singletons.insert::<SingletonCache<I>>(SingletonCache::new());
+            }
+            singletons
+                .get_mut::<SingletonCache<I>>()
+                .unwrap()
+                .entries
+                .insert(key.clone(), instance);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::Component;
+    use crate::container::ContainerBuilder;
+
+    trait Repo: Interface {
+        fn name(&self) -> &'static str;
+    }
+
+    struct SqliteRepo;
+    impl Repo for SqliteRepo {
+        fn name(&self) -> &'static str {
+            "sqlite"
+        }
+    }
+    impl Component for SqliteRepo {
+        type Interface = dyn Repo;
+        fn build(_: &Container) -> Box<Self::Interface> {
+            Box::new(SqliteRepo)
+        }
+    }
+
+    struct PostgresRepo;
+    impl Repo for PostgresRepo {
+        fn name(&self) -> &'static str {
+            "postgres"
+        }
+    }
+    impl Component for PostgresRepo {
+        type Interface = dyn Repo;
+        fn build(_: &Container) -> Box<Self::Interface> {
+            Box::new(PostgresRepo)
+        }
+    }
+
+    #[test]
+    fn named_bindings_coexist_independently_of_the_default_one() {
+        let mut builder = ContainerBuilder::new();
+        builder.register_named_type::<SqliteRepo>("sqlite");
+        builder.register_named_type::<PostgresRepo>("postgres");
+
+        let container = builder.build().unwrap();
+        assert_eq!(
+            container
+                .resolve_named::<dyn Repo>("sqlite")
+                .unwrap()
+                .name(),
+            "sqlite"
+        );
+        assert_eq!(
+            container
+                .resolve_named::<dyn Repo>("postgres")
+                .unwrap()
+                .name(),
+            "postgres"
+        );
+
+        // No default (unnamed) registration was made, and resolving an
+        // unknown name is an error rather than falling back to one.
+        assert!(container.resolve::<dyn Repo>().is_err());
+        assert!(container.resolve_named::<dyn Repo>("mysql").is_err());
+    }
+
+    trait Counter: Interface {
+        fn next(&self) -> usize;
+    }
+
+    struct CounterImpl {
+        calls: std::sync::atomic::AtomicUsize,
+    }
+    impl Counter for CounterImpl {
+        fn next(&self) -> usize {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+        }
+    }
+    impl Component for CounterImpl {
+        type Interface = dyn Counter;
+        fn build(_: &Container) -> Box<Self::Interface> {
+            Box::new(CounterImpl {
+                calls: std::sync::atomic::AtomicUsize::new(0),
+            })
+        }
+    }
+
+    #[test]
+    fn singleton_is_built_once_and_shared() {
+        let mut builder = ContainerBuilder::new();
+        builder.register_type::<CounterImpl>().as_singleton();
+        let container = builder.build().unwrap();
+
+        let first = container.resolve::<dyn Counter>().unwrap();
+        let second = container.resolve::<dyn Counter>().unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        // Both handles point at the same instance, so its internal state is shared.
+        assert_eq!(first.next(), 0);
+        assert_eq!(second.next(), 1);
+    }
+
+    #[test]
+    fn transient_is_rebuilt_on_every_resolve() {
+        let mut builder = ContainerBuilder::new();
+        builder.register_type::<CounterImpl>();
+        let container = builder.build().unwrap();
+
+        let first = container.resolve::<dyn Counter>().unwrap();
+        let second = container.resolve::<dyn Counter>().unwrap();
+        assert!(!Arc::ptr_eq(&first, &second));
+    }
+
+    trait Logger: Interface {
+        #[allow(dead_code)]
+        fn log(&self, msg: &str) -> String;
+    }
+
+    trait Greeter: Interface {
+        #[allow(dead_code)]
+        fn greet(&self) -> String;
+    }
+
+    struct GreeterImpl {
+        #[allow(dead_code)]
+        logger: Arc<dyn Logger>,
+    }
+    impl Greeter for GreeterImpl {
+        fn greet(&self) -> String {
+            self.logger.log("hi")
+        }
+    }
+    impl Component for GreeterImpl {
+        type Interface = dyn Greeter;
+        fn build(container: &Container) -> Box<Self::Interface> {
+            Box::new(GreeterImpl {
+                logger: container.resolve::<dyn Logger>().unwrap(),
+            })
+        }
+    }
+
+    #[test]
+    fn build_fails_eagerly_when_a_singletons_inject_dependency_is_missing() {
+        // `GreeterImpl` depends on `dyn Logger`, which is never registered;
+        // `build()`'s eager singleton validation should catch that here
+        // instead of letting the first `resolve::<dyn Greeter>()` panic.
+        let mut builder = ContainerBuilder::new();
+        builder.register_type::<GreeterImpl>().as_singleton();
+
+        assert!(builder.build().is_err());
+    }
+
+    #[cfg(feature = "thread_safe")]
+    #[test]
+    fn container_is_send_sync_and_shared_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Container>();
+
+        let mut builder = ContainerBuilder::new();
+        builder.register_type::<CounterImpl>().as_singleton();
+        let container = Arc::new(builder.build().unwrap());
+
+        let mut results: Vec<usize> = (0..4)
+            .map(|_| {
+                let container = container.clone();
+                std::thread::spawn(move || container.resolve::<dyn Counter>().unwrap().next())
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+        results.sort_unstable();
+
+        // One shared singleton, four threads each incrementing its counter.
+        assert_eq!(results, vec![0, 1, 2, 3]);
+    }
+}
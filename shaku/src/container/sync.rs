@@ -0,0 +1,47 @@
+//! Interior mutability for the singleton/instance caches, backed by a
+//! `RefCell` by default and a `Mutex` under the `thread_safe` Cargo feature
+//! so a built `Container` stays `Sync` when that feature is enabled.
+
+#[cfg(not(feature = "thread_safe"))]
+mod inner {
+    use std::cell::RefCell;
+
+    pub(crate) struct Lock<T>(RefCell<T>);
+
+    impl<T> Lock<T> {
+        pub(crate) fn new(value: T) -> Self {
+            Lock(RefCell::new(value))
+        }
+
+        pub(crate) fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+            f(&self.0.borrow())
+        }
+
+        pub(crate) fn with_mut<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+            f(&mut self.0.borrow_mut())
+        }
+    }
+}
+
+#[cfg(feature = "thread_safe")]
+mod inner {
+    use std::sync::Mutex;
+
+    pub(crate) struct Lock<T>(Mutex<T>);
+
+    impl<T> Lock<T> {
+        pub(crate) fn new(value: T) -> Self {
+            Lock(Mutex::new(value))
+        }
+
+        pub(crate) fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+            f(&self.0.lock().expect("shaku: Container lock poisoned"))
+        }
+
+        pub(crate) fn with_mut<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+            f(&mut self.0.lock().expect("shaku: Container lock poisoned"))
+        }
+    }
+}
+
+pub(crate) use inner::Lock;
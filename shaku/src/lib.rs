@@ -0,0 +1,28 @@
+//! # shaku
+//!
+//! `shaku` is a compile-time safe dependency injection library for Rust.
+//!
+//! Components are registered on a [`ContainerBuilder`], which is then built
+//! into a [`Container`] used to [`resolve`](container::Container::resolve)
+//! trait objects.
+//!
+//! ## Cargo features
+//!
+//! - `thread_safe`: requires every [`Interface`](component::Interface) to be
+//!   `Send + Sync`, so a built `Container` can be wrapped in an `Arc` and
+//!   shared across a thread pool or an async executor. Off by default, so
+//!   single-threaded users aren't forced to make every parameter
+//!   `Send + Sync`.
+
+#[macro_use]
+extern crate log;
+
+pub mod component;
+pub mod container;
+pub mod parameter;
+pub mod result;
+pub mod sync_bound;
+
+pub use crate::component::{Component, Interface};
+pub use crate::container::{Container, ContainerBuilder, RegisteredType};
+pub use crate::result::{Error, Result};
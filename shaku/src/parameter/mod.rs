@@ -0,0 +1,56 @@
+//! Parameters that can be attached to a registered
+//! [`Component`](../component/trait.Component.html).
+
+mod parameter_map;
+
+pub use self::parameter_map::ParameterMap;
+
+use std::any::{Any, TypeId};
+use std::fmt;
+
+use crate::sync_bound::MaybeSendSync;
+
+#[cfg(not(feature = "thread_safe"))]
+type Stored = dyn Any;
+#[cfg(feature = "thread_safe")]
+type Stored = dyn Any + Send + Sync;
+
+/// A type-erased parameter value attached to a registered Component via
+/// [`with_named_parameter`] or [`with_typed_parameter`]. It is not passed to
+/// `Component::build`.
+///
+/// [`with_named_parameter`]: ../container/struct.RegisteredType.html#method.with_named_parameter
+/// [`with_typed_parameter`]: ../container/struct.RegisteredType.html#method.with_typed_parameter
+pub struct Parameter {
+    name: String,
+    pub(crate) type_id: TypeId,
+    value: Box<Stored>,
+}
+
+impl fmt::Debug for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Parameter")
+            .field("name", &self.name)
+            .field("type_id", &self.type_id)
+            .finish()
+    }
+}
+
+impl Parameter {
+    pub(crate) fn new<V: Any + MaybeSendSync>(name: &str, value: V) -> Self {
+        Parameter {
+            name: name.to_string(),
+            type_id: TypeId::of::<V>(),
+            value: Box::new(value),
+        }
+    }
+
+    /// Name this parameter was registered under, for debugging purposes.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn get_value<V: Any + MaybeSendSync>(self) -> Option<V> {
+        self.value.downcast::<V>().ok().map(|value| *value)
+    }
+}
@@ -2,6 +2,7 @@ use std::any::{Any, TypeId};
 use std::collections::HashMap;
 
 use crate::parameter::*;
+use crate::sync_bound::MaybeSendSync;
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 enum Key {
@@ -28,7 +29,11 @@ impl ParameterMap {
 
     /// Insert a parameter based on property name. If a parameter was already inserted
     /// with that name and type (via this method), the old value is returned.
-    pub(crate) fn insert_with_name<V: Any>(&mut self, key: &str, value: V) -> Option<V> {
+    pub(crate) fn insert_with_name<V: Any + MaybeSendSync>(
+        &mut self,
+        key: &str,
+        value: V,
+    ) -> Option<V> {
         self.map
             .insert(Key::String(key.to_string()), Parameter::new(key, value))
             .and_then(Parameter::get_value)
@@ -36,7 +41,7 @@ impl ParameterMap {
 
     /// Insert a parameter based on property type. If a parameter was already inserted
     /// with that type (via this method), the old value is returned.
-    pub(crate) fn insert_with_type<V: Any>(&mut self, value: V) -> Option<V> {
+    pub(crate) fn insert_with_type<V: Any + MaybeSendSync>(&mut self, value: V) -> Option<V> {
         self.map
             .insert(
                 Key::Id(TypeId::of::<V>()),
@@ -49,7 +54,7 @@ impl ParameterMap {
     /// via [`with_named_parameter`]
     ///
     /// [`with_named_parameter`]: ../container/struct.RegisteredType.html#method.with_named_parameter
-    pub fn remove_with_name<V: Any>(&mut self, key: &str) -> Option<V> {
+    pub fn remove_with_name<V: Any + MaybeSendSync>(&mut self, key: &str) -> Option<V> {
         let key = Key::String(key.to_string());
         let parameter = self.map.get(&key)?;
 
@@ -64,7 +69,7 @@ impl ParameterMap {
     /// via [`with_typed_parameter`]
     ///
     /// [`with_typed_parameter`]: ../container/struct.RegisteredType.html#method.with_typed_parameter
-    pub fn remove_with_type<V: Any>(&mut self) -> Option<V> {
+    pub fn remove_with_type<V: Any + MaybeSendSync>(&mut self) -> Option<V> {
         let key = Key::Id(TypeId::of::<V>());
         let parameter = self.map.get(&key)?;
 
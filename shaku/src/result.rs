@@ -0,0 +1,27 @@
+//! Error and Result types used throughout the crate.
+
+use std::fmt;
+
+/// Errors that can occur while building or resolving a
+/// [`Container`](../container/struct.Container.html).
+#[derive(Debug)]
+pub enum Error {
+    /// No Component was registered for the requested interface (and name, if
+    /// any), or a singleton panicked while `ContainerBuilder::build` was
+    /// building it (e.g. a missing transitive `#[inject]` dependency).
+    ResolveError(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::ResolveError(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Convenience alias for a [`Result`](https://doc.rust-lang.org/std/result/enum.Result.html)
+/// using [`Error`](enum.Error.html).
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,23 @@
+//! Bridges the default single-threaded storage and the opt-in `thread_safe`
+//! mode (see the crate-level docs and the `thread_safe` Cargo feature).
+//!
+//! Everywhere a value is type-erased and stored inside the crate (parameters,
+//! registrations, singletons, ...), it is bounded by [`MaybeSendSync`]
+//! instead of a hardcoded `Send + Sync`, so the same code compiles whether
+//! or not the feature is on.
+//!
+//! This trait is `pub` (rather than `pub(crate)`) purely so it can appear in
+//! the bounds of public methods like
+//! [`RegisteredType::with_named_parameter`](../container/struct.RegisteredType.html#method.with_named_parameter)
+//! without tripping the `private_bounds` lint; it is blanket-implemented for
+//! every eligible type and is not meant to be implemented or named directly.
+
+#[cfg(not(feature = "thread_safe"))]
+pub trait MaybeSendSync {}
+#[cfg(not(feature = "thread_safe"))]
+impl<T: ?Sized> MaybeSendSync for T {}
+
+#[cfg(feature = "thread_safe")]
+pub trait MaybeSendSync: Send + Sync {}
+#[cfg(feature = "thread_safe")]
+impl<T: ?Sized + Send + Sync> MaybeSendSync for T {}
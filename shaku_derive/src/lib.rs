@@ -0,0 +1,164 @@
+//! `#[derive(Component)]`, generating an `impl Component for` the annotated
+//! struct (see `shaku::component::Component`).
+//!
+//! ```ignore
+//! #[derive(Component)]
+//! #[interface(Foo)]
+//! struct FooImpl {
+//!     #[inject]
+//!     bar: Arc<dyn Bar>,
+//!     retries: usize,
+//! }
+//! ```
+//!
+//! - `#[interface(...)]` (required) names the trait `Self::Interface`
+//!   resolves to (wrapped in `dyn`); it is parsed as a path, so a generic
+//!   Component can write `#[interface(Serializer<T>)]` to propagate its own
+//!   generic parameters.
+//! - `#[inject]` fields must be `Arc<dyn Trait>` (optionally generic); the
+//!   generated `build` resolves them from the `Container`, panicking with a
+//!   descriptive message if the dependency was never registered (the only
+//!   option open to it, since `Component::build` cannot return a `Result`).
+//! - Every other field is populated with `Default::default()`, since
+//!   `build` is not handed the `ParameterMap` attached to its registration.
+
+extern crate proc_macro;
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Fields, GenericArgument, Path, PathArguments, Type,
+};
+
+#[proc_macro_derive(Component, attributes(interface, inject))]
+pub fn derive_component(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let struct_name = &input.ident;
+    let interface = interface_attr(&input)?;
+    let fields = named_fields(&input)?;
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let self_ty = quote! { #struct_name #ty_generics };
+
+    // For a generic Component, the single derived impl covers every `T` the
+    // caller instantiates it with, but only `T`s for which the struct itself
+    // implements the interface trait (e.g. `impl Serializer<User> for
+    // JsonSerializer<User>`) actually produce a valid `Box<Self::Interface>`.
+    let where_clause = match where_clause {
+        Some(where_clause) => quote! { #where_clause, #self_ty: #interface },
+        None => quote! { where #self_ty: #interface },
+    };
+
+    let field_inits = fields
+        .iter()
+        .map(|field| field_init(struct_name, field))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        impl #impl_generics ::shaku::component::Component for #self_ty #where_clause {
+            type Interface = dyn #interface;
+
+            fn build(container: &::shaku::container::Container) -> ::std::boxed::Box<Self::Interface> {
+                ::std::boxed::Box::new(#struct_name {
+                    #(#field_inits,)*
+                })
+            }
+        }
+    })
+}
+
+/// Parse the required `#[interface(...)]` struct attribute into the trait
+/// path it names, e.g. `#[interface(Serializer<T>)]` -> `Serializer<T>`.
+/// `Self::Interface` is this path wrapped in `dyn`.
+fn interface_attr(input: &DeriveInput) -> syn::Result<Path> {
+    let attr = input
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("interface"))
+        .ok_or_else(|| {
+            syn::Error::new(
+                input.span(),
+                "#[derive(Component)] requires a `#[interface(SomeTrait)]` attribute",
+            )
+        })?;
+
+    attr.parse_args::<Path>()
+}
+
+fn named_fields(input: &DeriveInput) -> syn::Result<Vec<syn::Field>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.span(),
+            "#[derive(Component)] only supports structs",
+        ));
+    };
+
+    match &data.fields {
+        Fields::Named(fields) => Ok(fields.named.iter().cloned().collect()),
+        Fields::Unit => Ok(Vec::new()),
+        Fields::Unnamed(_) => Err(syn::Error::new(
+            data.fields.span(),
+            "#[derive(Component)] does not support tuple structs",
+        )),
+    }
+}
+
+fn is_inject(field: &syn::Field) -> bool {
+    field
+        .attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("inject"))
+}
+
+fn field_init(struct_name: &syn::Ident, field: &syn::Field) -> syn::Result<TokenStream2> {
+    let ident = field.ident.as_ref().expect("named field");
+
+    if !is_inject(field) {
+        return Ok(quote_spanned! {field.span()=>
+            #ident: ::std::default::Default::default()
+        });
+    }
+
+    let dependency = arc_inner_type(&field.ty).ok_or_else(|| {
+        syn::Error::new(
+            field.ty.span(),
+            "#[inject] fields must have type `Arc<dyn Trait>`",
+        )
+    })?;
+
+    Ok(quote_spanned! {field.span()=>
+        #ident: container.resolve::<#dependency>().unwrap_or_else(|err| {
+            panic!(
+                "shaku: failed to resolve #[inject] dependency `{}` of `{}`: {}",
+                stringify!(#ident),
+                stringify!(#struct_name),
+                err
+            )
+        })
+    })
+}
+
+/// Extract `T` out of `Arc<T>`, the type every `#[inject]` field must have.
+fn arc_inner_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Arc" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    args.args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    })
+}
@@ -0,0 +1,73 @@
+#![allow(dead_code)]
+
+extern crate shaku;
+#[macro_use]
+extern crate shaku_derive;
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use shaku::component::Interface;
+
+trait Logger: Interface {
+    fn log(&self, msg: &str) -> String;
+}
+
+trait Serializer<T>: Interface {
+    fn serialize(&self, value: &T) -> String;
+}
+
+#[derive(Component)]
+#[interface(Logger)]
+struct LoggerImpl;
+
+impl Logger for LoggerImpl {
+    fn log(&self, msg: &str) -> String {
+        msg.to_string()
+    }
+}
+
+// A generic Component: one `#[derive(Component)]` impl, usable for any `T`
+// the caller instantiates it with (e.g. `JsonSerializer<User>` and
+// `JsonSerializer<Order>` register and resolve independently).
+#[derive(Component)]
+#[interface(Serializer<T>)]
+struct JsonSerializer<T: 'static> {
+    #[inject]
+    logger: Arc<dyn Logger>,
+    _marker: PhantomData<T>,
+}
+
+struct User;
+struct Order;
+
+impl Serializer<User> for JsonSerializer<User> {
+    fn serialize(&self, _value: &User) -> String {
+        self.logger.log("serializing a User")
+    }
+}
+
+impl Serializer<Order> for JsonSerializer<Order> {
+    fn serialize(&self, _value: &Order) -> String {
+        self.logger.log("serializing an Order")
+    }
+}
+
+#[test]
+fn compile_ok() {}
+
+#[test]
+fn resolves_generic_component_per_instantiation_with_injected_dependency() {
+    let mut builder = shaku::ContainerBuilder::new();
+    builder.register_type::<LoggerImpl>();
+    builder.register_type::<JsonSerializer<User>>();
+    builder.register_type::<JsonSerializer<Order>>();
+
+    let container = builder.build().unwrap();
+
+    let user_serializer = container.resolve::<dyn Serializer<User>>().unwrap();
+    let order_serializer = container.resolve::<dyn Serializer<Order>>().unwrap();
+
+    assert_eq!(user_serializer.serialize(&User), "serializing a User");
+    assert_eq!(order_serializer.serialize(&Order), "serializing an Order");
+}
@@ -4,14 +4,18 @@ extern crate shaku;
 #[macro_use]
 extern crate shaku_derive;
 
+use std::sync::Arc;
+
+use shaku::component::Interface;
+
 #[derive(Component)]
 #[interface(Foo)]
 struct TestComponent {
     var1: String,
     var2: usize,
-    var3: Box<String>,
+    var3: String,
     #[inject]
-    var5: Box<dyn Bar>,
+    var5: Arc<dyn Bar>,
 }
 
 #[derive(Component)]
@@ -20,25 +24,21 @@ struct BarImpl {
     val: usize,
 }
 
-trait Foo: Send {
+trait Foo: Interface {
     fn foo(&self);
 }
 
-trait Bar: Send {
+trait Bar: Interface {
     fn bar(&self);
 }
 
 impl Foo for TestComponent {
-    fn foo(&self) {
-        ()
-    }
+    fn foo(&self) {}
 }
 
 impl Bar for BarImpl {
-    fn bar(&self) {
-        ()
-    }
+    fn bar(&self) {}
 }
 
 #[test]
-fn compile_ok() {}
\ No newline at end of file
+fn compile_ok() {}